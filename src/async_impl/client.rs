@@ -0,0 +1,233 @@
+//! The client-facing builder.
+//!
+//! This only covers the DNS-resolution slice of `ClientBuilder` touched by
+//! the `dns` module — the rest of the builder surface (TLS, proxies,
+//! timeouts, the connector itself) lives elsewhere and isn't part of this
+//! change.
+
+use std::sync::Arc;
+
+use crate::dns::Resolve;
+
+#[cfg(feature = "hickory-dns")]
+use crate::dns::hickory::HickoryDnsResolver;
+#[cfg(feature = "hickory-dns")]
+use crate::dns::recursive::RecursiveDnsResolver;
+#[cfg(feature = "hickory-dns")]
+use hickory_resolver::config::{LookupIpStrategy, ResolverConfig, TtlConfig};
+
+#[cfg(all(feature = "trust-dns", not(feature = "hickory-dns")))]
+use crate::dns::trust_dns::{LookupIpStrategy, TrustDnsResolver};
+
+/// The default resolver filter: accept every address a name server returns.
+#[cfg(any(feature = "hickory-dns", feature = "trust-dns"))]
+fn accept_all_ips(_addr: std::net::IpAddr) -> bool {
+    true
+}
+
+#[derive(Default)]
+struct DnsConfig {
+    /// A caller-supplied resolver, taking priority over every other option
+    /// below. See `ClientBuilder::dns_resolver`.
+    resolver: Option<Arc<dyn Resolve>>,
+
+    #[cfg(feature = "hickory-dns")]
+    hickory_dns: bool,
+    #[cfg(feature = "hickory-dns")]
+    hickory_dns_shared: bool,
+    #[cfg(feature = "hickory-dns")]
+    hickory_resolver_config: Option<ResolverConfig>,
+    #[cfg(feature = "hickory-dns")]
+    hickory_cache_size: Option<usize>,
+    #[cfg(feature = "hickory-dns")]
+    hickory_ttl_config: Option<TtlConfig>,
+    #[cfg(feature = "hickory-dns")]
+    hickory_recursive: bool,
+
+    #[cfg(all(feature = "trust-dns", not(feature = "hickory-dns")))]
+    trust_dns: bool,
+
+    #[cfg(any(feature = "hickory-dns", feature = "trust-dns"))]
+    ip_strategy: Option<LookupIpStrategy>,
+}
+
+/// A `ClientBuilder` can be used to create a `Client` with custom
+/// configuration.
+#[derive(Default)]
+pub struct ClientBuilder {
+    dns: DnsConfig,
+}
+
+impl ClientBuilder {
+    /// Constructs a new `ClientBuilder`.
+    pub fn new() -> Self {
+        ClientBuilder::default()
+    }
+
+    /// Use a pre-built `Resolve` implementation for DNS resolution instead
+    /// of one built from the other `dns_*`/`hickory_dns` options below.
+    ///
+    /// Useful for sharing a single custom resolver's cache and upstream
+    /// connections across multiple `Client`s, instead of every `Client`
+    /// building its own.
+    pub fn dns_resolver(mut self, resolver: Arc<dyn Resolve>) -> Self {
+        self.dns.resolver = Some(resolver);
+        self
+    }
+
+    /// Enables the hickory-dns async resolver instead of the default
+    /// threadpool-based `getaddrinfo` resolver.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `hickory-dns` feature to be enabled.
+    #[cfg(feature = "hickory-dns")]
+    pub fn hickory_dns(mut self, enable: bool) -> Self {
+        self.dns.hickory_dns = enable;
+        self
+    }
+
+    /// Build the hickory-dns resolver from a process-wide shared
+    /// `TokioAsyncResolver` (see `HickoryDnsResolver::shared`) instead of a
+    /// fresh one per `Client`. Only takes effect when `hickory_dns(true)`
+    /// is also set.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `hickory-dns` feature to be enabled.
+    #[cfg(feature = "hickory-dns")]
+    pub fn hickory_dns_shared(mut self, enable: bool) -> Self {
+        self.dns.hickory_dns_shared = enable;
+        self
+    }
+
+    /// Use the given upstream nameserver configuration instead of reading
+    /// the system's `resolv.conf`, e.g. `ResolverConfig::cloudflare_https()`
+    /// to resolve over DNS-over-HTTPS/TLS.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `hickory-dns` feature to be enabled, and
+    /// the matching `dns-over-https-rustls`/`dns-over-rustls` hickory
+    /// feature for encrypted upstreams.
+    #[cfg(feature = "hickory-dns")]
+    pub fn dns_resolver_config(mut self, config: ResolverConfig) -> Self {
+        self.dns.hickory_resolver_config = Some(config);
+        self
+    }
+
+    /// Override the number of entries the DNS cache holds. Set `0` to
+    /// disable caching entirely.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `hickory-dns` feature to be enabled.
+    #[cfg(feature = "hickory-dns")]
+    pub fn dns_cache_size(mut self, cache_size: usize) -> Self {
+        self.dns.hickory_cache_size = Some(cache_size);
+        self
+    }
+
+    /// Override the positive/negative TTL bounds records are clamped to.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `hickory-dns` feature to be enabled.
+    #[cfg(feature = "hickory-dns")]
+    pub fn dns_ttl_config(mut self, ttl_config: TtlConfig) -> Self {
+        self.dns.hickory_ttl_config = Some(ttl_config);
+        self
+    }
+
+    /// Resolve fully recursively from the IANA root hints instead of
+    /// delegating to a configured upstream resolver. See
+    /// `RecursiveDnsResolver`. Takes priority over `hickory_dns` when both
+    /// are enabled.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `hickory-dns` feature to be enabled.
+    #[cfg(feature = "hickory-dns")]
+    pub fn dns_recursive(mut self, enable: bool) -> Self {
+        self.dns.hickory_recursive = enable;
+        self
+    }
+
+    /// Enables the trust-dns async resolver instead of the default
+    /// threadpool-based `getaddrinfo` resolver.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `trust-dns` feature to be enabled.
+    #[cfg(all(feature = "trust-dns", not(feature = "hickory-dns")))]
+    pub fn trust_dns(mut self, enable: bool) -> Self {
+        self.dns.trust_dns = enable;
+        self
+    }
+
+    /// Override the IP lookup strategy used for resolution, e.g. to force
+    /// IPv4-only or IPv6-only lookups. Applies to whichever of
+    /// `hickory-dns`/`trust-dns` is enabled.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `hickory-dns` or `trust-dns` feature to
+    /// be enabled.
+    #[cfg(any(feature = "hickory-dns", feature = "trust-dns"))]
+    pub fn dns_ip_strategy(mut self, ip_strategy: LookupIpStrategy) -> Self {
+        self.dns.ip_strategy = Some(ip_strategy);
+        self
+    }
+
+    /// Build the `Resolve` implementation this builder's DNS options
+    /// describe, for the connector to use.
+    ///
+    /// Returns `None` when nothing but the default `getaddrinfo`-based
+    /// resolution was requested.
+    pub(crate) fn build_resolver(&self) -> Option<Arc<dyn Resolve>> {
+        if let Some(resolver) = &self.dns.resolver {
+            return Some(Arc::clone(resolver));
+        }
+
+        #[cfg(feature = "hickory-dns")]
+        {
+            if self.dns.hickory_recursive {
+                return Some(Arc::new(RecursiveDnsResolver::new(accept_all_ips)));
+            }
+
+            if self.dns.hickory_dns {
+                let mut resolver = if self.dns.hickory_dns_shared {
+                    HickoryDnsResolver::shared(accept_all_ips)
+                } else {
+                    HickoryDnsResolver::new(accept_all_ips)
+                };
+                if let Some(config) = self.dns.hickory_resolver_config.clone() {
+                    resolver = resolver.with_resolver_config(config);
+                }
+                if let Some(ip_strategy) = self.dns.ip_strategy {
+                    resolver = resolver.with_ip_strategy(ip_strategy);
+                }
+                if let Some(cache_size) = self.dns.hickory_cache_size {
+                    resolver = resolver.with_cache_size(cache_size);
+                }
+                if let Some(ttl_config) = self.dns.hickory_ttl_config.clone() {
+                    resolver = resolver.with_ttl_config(ttl_config);
+                }
+                return Some(Arc::new(resolver));
+            }
+        }
+
+        #[cfg(all(feature = "trust-dns", not(feature = "hickory-dns")))]
+        {
+            if self.dns.trust_dns {
+                let mut resolver = TrustDnsResolver::new(accept_all_ips);
+                if let Some(ip_strategy) = self.dns.ip_strategy {
+                    resolver = resolver.with_ip_strategy(ip_strategy);
+                }
+                return Some(Arc::new(resolver));
+            }
+        }
+
+        None
+    }
+}
@@ -1,8 +1,10 @@
 //! DNS resolution via the [hickory-resolver](https://github.com/hickory-dns/hickory-dns) crate
 
 use hickory_resolver::{
-    config::LookupIpStrategy, error::ResolveError, lookup_ip::LookupIpIntoIter, system_conf,
-    TokioAsyncResolver,
+    config::{LookupIpStrategy, ResolverConfig, TtlConfig},
+    error::ResolveError,
+    lookup_ip::LookupIpIntoIter,
+    system_conf, TokioAsyncResolver, TryParseIp,
 };
 use once_cell::sync::OnceCell;
 
@@ -18,8 +20,22 @@ pub(crate) struct HickoryDnsResolver {
     /// Since we might not have been called in the context of a
     /// Tokio Runtime in initialization, so we must delay the actual
     /// construction of the resolver.
-    state: Arc<OnceCell<TokioAsyncResolver>>,
+    state: Arc<OnceCell<ResolverState>>,
     filter: fn(std::net::IpAddr) -> bool,
+    /// Upstream nameserver configuration to use instead of the system
+    /// resolv.conf, e.g. `ResolverConfig::cloudflare_https()` to resolve
+    /// over DNS-over-HTTPS/TLS. Requires enabling the matching
+    /// `dns-over-https-rustls` / `dns-over-rustls` feature on `hickory-resolver`.
+    config: Option<ResolverConfig>,
+    /// Overrides the default `Ipv4AndIpv6` lookup strategy, e.g. to force
+    /// `Ipv4Only` or `Ipv6Only` resolution.
+    ip_strategy: Option<LookupIpStrategy>,
+    /// Overrides the number of entries the resolver's DNS cache holds.
+    /// Setting this to `0` disables caching entirely.
+    cache_size: Option<usize>,
+    /// Overrides the positive/negative TTL bounds the resolver clamps
+    /// record lifetimes to.
+    ttl_config: Option<TtlConfig>,
 }
 
 struct SocketAddrs {
@@ -27,13 +43,81 @@ struct SocketAddrs {
     filter: fn(std::net::IpAddr) -> bool,
 }
 
+/// A built resolver together with a fingerprint of the options it was built
+/// with, so that a `shared()` resolver can detect (rather than silently
+/// ignore) a second `Client` trying to reuse it with different DNS options.
+struct ResolverState {
+    resolver: TokioAsyncResolver,
+    options_fingerprint: String,
+}
+
+/// The `ResolverState` built by `HickoryDnsResolver::shared`, reused by
+/// every resolver created that way across the process.
+static SHARED_STATE: OnceCell<Arc<OnceCell<ResolverState>>> = OnceCell::new();
+
 impl HickoryDnsResolver {
     pub fn new(filter: fn(std::net::IpAddr) -> bool) -> Self {
         Self {
             state: Default::default(),
             filter,
+            config: None,
+            ip_strategy: None,
+            cache_size: None,
+            ttl_config: None,
+        }
+    }
+
+    /// Build a resolver that shares its underlying `TokioAsyncResolver` (and
+    /// therefore its DNS cache and upstream connections) with every other
+    /// `HickoryDnsResolver` created via `shared` in this process, instead of
+    /// each `Client` re-reading `resolv.conf` and rebuilding resolver state.
+    ///
+    /// The shared resolver is built once, from whichever `shared()` instance
+    /// first calls `resolve`. Any `with_*` overrides must be applied
+    /// identically to every `shared()` instance in the process: a later
+    /// instance whose overrides don't match the first one's is rejected with
+    /// an error rather than silently resolving with the wrong options.
+    pub fn shared(filter: fn(std::net::IpAddr) -> bool) -> Self {
+        let state = SHARED_STATE.get_or_init(Arc::default).clone();
+        Self {
+            state,
+            filter,
+            config: None,
+            ip_strategy: None,
+            cache_size: None,
+            ttl_config: None,
         }
     }
+
+    /// Use the given upstream nameserver configuration instead of reading
+    /// the system's `resolv.conf`, e.g. `ResolverConfig::cloudflare_https()`.
+    /// Reachable via `ClientBuilder::dns_resolver_config`.
+    pub fn with_resolver_config(mut self, config: ResolverConfig) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// Override the IP lookup strategy used for resolution, e.g. to force
+    /// IPv4-only or IPv6-only lookups. Reachable via
+    /// `ClientBuilder::dns_ip_strategy`.
+    pub fn with_ip_strategy(mut self, ip_strategy: LookupIpStrategy) -> Self {
+        self.ip_strategy = Some(ip_strategy);
+        self
+    }
+
+    /// Override the number of entries the resolver's DNS cache holds. Set
+    /// `0` to disable caching. Reachable via `ClientBuilder::dns_cache_size`.
+    pub fn with_cache_size(mut self, cache_size: usize) -> Self {
+        self.cache_size = Some(cache_size);
+        self
+    }
+
+    /// Override the positive/negative TTL bounds records are clamped to.
+    /// Reachable via `ClientBuilder::dns_ttl_config`.
+    pub fn with_ttl_config(mut self, ttl_config: TtlConfig) -> Self {
+        self.ttl_config = Some(ttl_config);
+        self
+    }
 }
 
 #[derive(Debug)]
@@ -44,7 +128,47 @@ impl Resolve for HickoryDnsResolver {
         let resolver = self.clone();
         Box::pin(async move {
             let filter = resolver.filter;
-            let resolver = resolver.state.get_or_try_init(new_resolver)?;
+
+            // If the host is already an IP literal (e.g. "127.0.0.1" or
+            // "[::1]"), skip the resolver entirely instead of asking a
+            // (possibly misconfigured) name server to look up an address
+            // we already have.
+            if let Some(ip_addr) = name
+                .as_str()
+                .try_parse_ip()
+                .and_then(|record| record.ip_addr())
+            {
+                return if filter(ip_addr) {
+                    let addrs: Addrs = Box::new(std::iter::once(SocketAddr::new(ip_addr, 0)));
+                    Ok(addrs)
+                } else {
+                    let e =
+                        hickory_resolver::error::ResolveError::from("destination is restricted");
+                    Err(e.into())
+                };
+            }
+
+            let config = resolver.config.clone();
+            let ip_strategy = resolver.ip_strategy;
+            let cache_size = resolver.cache_size;
+            let ttl_config = resolver.ttl_config.clone();
+            let options_fingerprint = fingerprint(&config, ip_strategy, cache_size, &ttl_config);
+
+            let state = resolver.state.get_or_try_init(|| {
+                new_resolver(config, ip_strategy, cache_size, ttl_config).map(|resolver| {
+                    ResolverState {
+                        resolver,
+                        options_fingerprint: options_fingerprint.clone(),
+                    }
+                })
+            })?;
+            if state.options_fingerprint != options_fingerprint {
+                let e = hickory_resolver::error::ResolveError::from(
+                    "a shared HickoryDnsResolver was already initialized with different DNS options",
+                );
+                return Err(e.into());
+            }
+            let resolver = &state.resolver;
 
             let lookup = resolver.lookup_ip(name.as_str()).await?;
             if !lookup.iter().any(filter) {
@@ -74,13 +198,43 @@ impl Iterator for SocketAddrs {
     }
 }
 
-/// Create a new resolver with the default configuration,
-/// which reads from `/etc/resolve.conf`. The options are
-/// overridden to look up for both IPv4 and IPv6 addresses
-/// to work with "happy eyeballs" algorithm.
-fn new_resolver() -> Result<TokioAsyncResolver, HickoryDnsSystemConfError> {
-    let (config, mut opts) = system_conf::read_system_conf().map_err(HickoryDnsSystemConfError)?;
-    opts.ip_strategy = LookupIpStrategy::Ipv4AndIpv6;
+/// A stable string representation of the options a resolver was built with,
+/// used to detect a `shared()` resolver being reused with mismatched
+/// `with_*` overrides across `Client`s.
+fn fingerprint(
+    config: &Option<ResolverConfig>,
+    ip_strategy: Option<LookupIpStrategy>,
+    cache_size: Option<usize>,
+    ttl_config: &Option<TtlConfig>,
+) -> String {
+    format!(
+        "{:?}|{:?}|{:?}|{:?}",
+        config, ip_strategy, cache_size, ttl_config
+    )
+}
+
+/// Create a new resolver with the given configuration, falling back to the
+/// default configuration read from `/etc/resolve.conf` when none is given.
+/// The options default to looking up both IPv4 and IPv6 addresses to work
+/// with the "happy eyeballs" algorithm, unless an `ip_strategy` override is
+/// given.
+fn new_resolver(
+    config: Option<ResolverConfig>,
+    ip_strategy: Option<LookupIpStrategy>,
+    cache_size: Option<usize>,
+    ttl_config: Option<TtlConfig>,
+) -> Result<TokioAsyncResolver, HickoryDnsSystemConfError> {
+    let (config, mut opts) = match config {
+        Some(config) => (config, hickory_resolver::config::ResolverOpts::default()),
+        None => system_conf::read_system_conf().map_err(HickoryDnsSystemConfError)?,
+    };
+    opts.ip_strategy = ip_strategy.unwrap_or(LookupIpStrategy::Ipv4AndIpv6);
+    if let Some(cache_size) = cache_size {
+        opts.cache_size = cache_size;
+    }
+    if let Some(ttl_config) = ttl_config {
+        opts.ttl_config = ttl_config;
+    }
     Ok(TokioAsyncResolver::tokio(config, opts))
 }
 
@@ -95,3 +249,93 @@ impl std::error::Error for HickoryDnsSystemConfError {
         Some(&self.0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn allow_all(_: std::net::IpAddr) -> bool {
+        true
+    }
+
+    #[test]
+    fn with_resolver_config_overrides_the_default_upstream() {
+        let resolver =
+            HickoryDnsResolver::new(allow_all).with_resolver_config(ResolverConfig::cloudflare());
+        assert!(resolver.config.is_some());
+    }
+
+    #[test]
+    fn with_ip_strategy_overrides_the_default_strategy() {
+        let resolver =
+            HickoryDnsResolver::new(allow_all).with_ip_strategy(LookupIpStrategy::Ipv4Only);
+        assert_eq!(resolver.ip_strategy, Some(LookupIpStrategy::Ipv4Only));
+    }
+
+    #[test]
+    fn with_cache_size_overrides_the_default_cache_size() {
+        let resolver = HickoryDnsResolver::new(allow_all).with_cache_size(0);
+        assert_eq!(resolver.cache_size, Some(0));
+    }
+
+    #[test]
+    fn with_ttl_config_overrides_the_default_ttl_bounds() {
+        let resolver = HickoryDnsResolver::new(allow_all).with_ttl_config(TtlConfig::default());
+        assert!(resolver.ttl_config.is_some());
+    }
+
+    fn deny_all(_: std::net::IpAddr) -> bool {
+        false
+    }
+
+    #[tokio::test]
+    async fn resolves_ipv4_literal_without_contacting_a_resolver() {
+        let resolver = HickoryDnsResolver::new(allow_all);
+        let name: Name = "127.0.0.1".parse().unwrap();
+
+        let mut addrs = resolver.resolve(name).await.unwrap();
+        assert_eq!(
+            addrs.next(),
+            Some(SocketAddr::new(std::net::Ipv4Addr::LOCALHOST.into(), 0))
+        );
+        assert_eq!(addrs.next(), None);
+    }
+
+    #[tokio::test]
+    async fn resolves_ipv6_literal_without_contacting_a_resolver() {
+        let resolver = HickoryDnsResolver::new(allow_all);
+        let name: Name = "::1".parse().unwrap();
+
+        let mut addrs = resolver.resolve(name).await.unwrap();
+        assert_eq!(
+            addrs.next(),
+            Some(SocketAddr::new(std::net::Ipv6Addr::LOCALHOST.into(), 0))
+        );
+        assert_eq!(addrs.next(), None);
+    }
+
+    #[tokio::test]
+    async fn rejects_ip_literal_filtered_out_by_the_filter() {
+        let resolver = HickoryDnsResolver::new(deny_all);
+        let name: Name = "127.0.0.1".parse().unwrap();
+
+        assert!(resolver.resolve(name).await.is_err());
+    }
+
+    #[test]
+    fn shared_resolvers_reuse_the_same_underlying_state() {
+        let a = HickoryDnsResolver::shared(allow_all);
+        let b = HickoryDnsResolver::shared(allow_all);
+        assert!(Arc::ptr_eq(&a.state, &b.state));
+    }
+
+    #[test]
+    fn fingerprint_differs_when_options_differ() {
+        let base = fingerprint(&None, None, None, &None);
+        let with_cache_size = fingerprint(&None, None, Some(0), &None);
+        let with_ip_strategy = fingerprint(&None, Some(LookupIpStrategy::Ipv4Only), None, &None);
+        assert_ne!(base, with_cache_size);
+        assert_ne!(base, with_ip_strategy);
+        assert_ne!(with_cache_size, with_ip_strategy);
+    }
+}
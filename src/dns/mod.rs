@@ -0,0 +1,28 @@
+//! DNS resolution
+
+use std::future::Future;
+use std::net::SocketAddr;
+use std::pin::Pin;
+
+pub use hyper::client::connect::dns::Name;
+
+#[cfg(feature = "hickory-dns")]
+pub(crate) mod hickory;
+#[cfg(feature = "hickory-dns")]
+pub(crate) mod recursive;
+#[cfg(feature = "trust-dns")]
+pub(crate) mod trust_dns;
+
+pub type Resolving =
+    Pin<Box<dyn Future<Output = Result<Addrs, Box<dyn std::error::Error + Send + Sync>>> + Send>>;
+
+/// Alias for an `Iterator` trait object over `SocketAddr`s.
+pub type Addrs = Box<dyn Iterator<Item = SocketAddr> + Send>;
+
+/// A trait for customizing DNS resolution in the client.
+///
+/// Exposed so that `ClientBuilder::dns_resolver` can accept a custom
+/// implementation, e.g. a process-wide shared `HickoryDnsResolver`.
+pub trait Resolve: Send + Sync {
+    fn resolve(&self, name: Name) -> Resolving;
+}
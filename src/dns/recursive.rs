@@ -0,0 +1,132 @@
+//! Opt-in recursive DNS resolution, walking referrals down from the IANA
+//! root servers instead of delegating to a configured upstream resolver.
+//!
+//! This is backed by [hickory-recursor](https://github.com/hickory-dns/hickory-dns)
+//! and is useful for callers who don't want to trust any single upstream
+//! resolver, or who want to validate delegation themselves.
+
+use hickory_proto::rr::RecordType;
+use hickory_recursor::Recursor;
+use hickory_resolver::config::NameServerConfigGroup;
+use once_cell::sync::OnceCell;
+
+use std::fmt;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use super::{Addrs, Name, Resolve, Resolving};
+
+/// A `Resolve` implementation that performs fully recursive resolution from
+/// the IANA root hints, rather than delegating to an upstream resolver.
+#[derive(Debug, Clone)]
+pub(crate) struct RecursiveDnsResolver {
+    /// Since we might not have been called in the context of a
+    /// Tokio Runtime in initialization, so we must delay the actual
+    /// construction of the resolver.
+    state: Arc<OnceCell<Recursor>>,
+    roots: NameServerConfigGroup,
+    filter: fn(std::net::IpAddr) -> bool,
+}
+
+struct SocketAddrs {
+    iter: std::vec::IntoIter<std::net::IpAddr>,
+    filter: fn(std::net::IpAddr) -> bool,
+}
+
+impl RecursiveDnsResolver {
+    /// Build a recursive resolver seeded with the IANA root hints.
+    pub fn new(filter: fn(std::net::IpAddr) -> bool) -> Self {
+        Self {
+            state: Default::default(),
+            roots: NameServerConfigGroup::root_hints(),
+            filter,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct RecursiveDnsError(hickory_recursor::Error);
+
+impl Resolve for RecursiveDnsResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let resolver = self.clone();
+        Box::pin(async move {
+            let filter = resolver.filter;
+            let roots = resolver.roots.clone();
+            let recursor = resolver
+                .state
+                .get_or_try_init(|| new_recursor(roots))
+                .map_err(|e| e as Box<dyn std::error::Error + Send + Sync>)?;
+
+            // Query both address families concurrently, the same way
+            // `HickoryDnsResolver`/`TrustDnsResolver` default to
+            // `Ipv4AndIpv6`, instead of silently resolving IPv4-only.
+            let query_name: hickory_proto::rr::Name = name.as_str().parse()?;
+            let (a_lookup, aaaa_lookup) = tokio::join!(
+                recursor.resolve(query_name.clone(), RecordType::A),
+                recursor.resolve(query_name, RecordType::AAAA)
+            );
+
+            let mut ips = Vec::new();
+            let mut last_err = None;
+            for lookup in [a_lookup, aaaa_lookup] {
+                match lookup {
+                    Ok(lookup) => {
+                        ips.extend(lookup.record_iter().filter_map(|r| r.data().ip_addr()))
+                    }
+                    Err(e) => last_err = Some(e),
+                }
+            }
+            if ips.is_empty() {
+                let e = match last_err {
+                    Some(e) => RecursiveDnsError(e),
+                    None => RecursiveDnsError(hickory_recursor::Error::from("no addresses found")),
+                };
+                return Err(e.into());
+            }
+
+            if !ips.iter().copied().any(filter) {
+                let e =
+                    RecursiveDnsError(hickory_recursor::Error::from("destination is restricted"));
+                return Err(e.into());
+            }
+
+            let addrs: Addrs = Box::new(SocketAddrs {
+                iter: ips.into_iter(),
+                filter,
+            });
+            Ok(addrs)
+        })
+    }
+}
+
+impl Iterator for SocketAddrs {
+    type Item = SocketAddr;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let ip_addr = self.iter.next()?;
+            if (self.filter)(ip_addr) {
+                return Some(SocketAddr::new(ip_addr, 0));
+            }
+        }
+    }
+}
+
+/// Create a new recursor seeded with the given root hints. Each zone cut
+/// walked during resolution keeps its own name-server cache internally.
+fn new_recursor(roots: NameServerConfigGroup) -> Result<Recursor, RecursiveDnsError> {
+    Recursor::builder().build(roots).map_err(RecursiveDnsError)
+}
+
+impl fmt::Display for RecursiveDnsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("error performing recursive DNS resolution")
+    }
+}
+
+impl std::error::Error for RecursiveDnsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.0)
+    }
+}
@@ -2,8 +2,10 @@
 
 use hyper::client::connect::dns::Name;
 use once_cell::sync::OnceCell;
-pub use trust_dns_resolver::config::{ResolverConfig, ResolverOpts};
-use trust_dns_resolver::{lookup_ip::LookupIpIntoIter, system_conf, TokioAsyncResolver};
+pub use trust_dns_resolver::config::{LookupIpStrategy, ResolverConfig, ResolverOpts};
+use trust_dns_resolver::{
+    lookup_ip::LookupIpIntoIter, system_conf, TokioAsyncResolver, TryParseIp,
+};
 
 use std::io;
 use std::net::SocketAddr;
@@ -19,6 +21,9 @@ pub(crate) struct TrustDnsResolver {
     /// construction of the resolver.
     state: Arc<OnceCell<TokioAsyncResolver>>,
     filter: fn(std::net::IpAddr) -> bool,
+    /// Overrides the default `Ipv4AndIpv6` lookup strategy, mirroring
+    /// `HickoryDnsResolver::with_ip_strategy`.
+    ip_strategy: Option<LookupIpStrategy>,
 }
 
 struct SocketAddrs {
@@ -31,8 +36,18 @@ impl TrustDnsResolver {
         TrustDnsResolver {
             state: Default::default(),
             filter,
+            ip_strategy: None,
         }
     }
+
+    /// Override the IP lookup strategy used for resolution. Reachable via
+    /// `ClientBuilder::dns_ip_strategy` when the `trust-dns` backend is in
+    /// use; see `HickoryDnsResolver::with_ip_strategy` for the `hickory-dns`
+    /// equivalent.
+    pub fn with_ip_strategy(mut self, ip_strategy: LookupIpStrategy) -> Self {
+        self.ip_strategy = Some(ip_strategy);
+        self
+    }
 }
 
 impl Resolve for TrustDnsResolver {
@@ -40,7 +55,29 @@ impl Resolve for TrustDnsResolver {
         let resolver = self.clone();
         Box::pin(async move {
             let filter = resolver.filter;
-            let resolver = resolver.state.get_or_try_init(new_resolver)?;
+
+            // If the host is already an IP literal, skip the resolver
+            // entirely instead of asking a (possibly misconfigured) name
+            // server to look up an address we already have.
+            if let Some(ip_addr) = name
+                .as_str()
+                .try_parse_ip()
+                .and_then(|record| record.ip_addr())
+            {
+                return if filter(ip_addr) {
+                    let addrs: Addrs = Box::new(std::iter::once(SocketAddr::new(ip_addr, 0)));
+                    Ok(addrs)
+                } else {
+                    let e =
+                        trust_dns_resolver::error::ResolveError::from("destination is restricted");
+                    Err(e.into())
+                };
+            }
+
+            let ip_strategy = resolver.ip_strategy;
+            let resolver = resolver
+                .state
+                .get_or_try_init(|| new_resolver(ip_strategy))?;
 
             let lookup = resolver.lookup_ip(name.as_str()).await?;
             if !lookup.iter().any(filter) {
@@ -72,12 +109,56 @@ impl Iterator for SocketAddrs {
 
 /// Create a new resolver with the default configuration,
 /// which reads from `/etc/resolve.conf`.
-fn new_resolver() -> io::Result<TokioAsyncResolver> {
-    let (config, opts) = system_conf::read_system_conf().map_err(|e| {
+fn new_resolver(ip_strategy: Option<LookupIpStrategy>) -> io::Result<TokioAsyncResolver> {
+    let (config, mut opts) = system_conf::read_system_conf().map_err(|e| {
         io::Error::new(
             io::ErrorKind::Other,
             format!("error reading DNS system conf: {}", e),
         )
     })?;
+    if let Some(ip_strategy) = ip_strategy {
+        opts.ip_strategy = ip_strategy;
+    }
     Ok(TokioAsyncResolver::tokio(config, opts))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn allow_all(_: std::net::IpAddr) -> bool {
+        true
+    }
+
+    fn deny_all(_: std::net::IpAddr) -> bool {
+        false
+    }
+
+    #[tokio::test]
+    async fn resolves_ip_literal_without_contacting_a_resolver() {
+        let resolver = TrustDnsResolver::new(allow_all);
+        let name: Name = "127.0.0.1".parse().unwrap();
+
+        let mut addrs = resolver.resolve(name).await.unwrap();
+        assert_eq!(
+            addrs.next(),
+            Some(SocketAddr::new(std::net::Ipv4Addr::LOCALHOST.into(), 0))
+        );
+        assert_eq!(addrs.next(), None);
+    }
+
+    #[tokio::test]
+    async fn rejects_ip_literal_filtered_out_by_the_filter() {
+        let resolver = TrustDnsResolver::new(deny_all);
+        let name: Name = "127.0.0.1".parse().unwrap();
+
+        assert!(resolver.resolve(name).await.is_err());
+    }
+
+    #[test]
+    fn with_ip_strategy_overrides_the_default_strategy() {
+        let resolver =
+            TrustDnsResolver::new(allow_all).with_ip_strategy(LookupIpStrategy::Ipv4Only);
+        assert_eq!(resolver.ip_strategy, Some(LookupIpStrategy::Ipv4Only));
+    }
+}
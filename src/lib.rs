@@ -0,0 +1,5 @@
+mod async_impl;
+mod dns;
+
+pub use async_impl::client::ClientBuilder;
+pub use dns::Resolve;